@@ -1,8 +1,12 @@
 //! Command line interface.
 
 use chrono::{Duration, Utc};
-use colored::Colorize;
-use std::{error::Error, fmt::Display, iter::once, path::PathBuf};
+use colored::{ColoredString, Colorize};
+use std::{
+  error::Error,
+  io::{self, Write},
+  path::PathBuf,
+};
 use structopt::StructOpt;
 
 use crate::{
@@ -74,6 +78,32 @@ pub enum SubCommand {
     all: bool,
   },
 
+  /// Synchronize the task store with a git remote.
+  Sync {
+    /// Remote to synchronize with.
+    #[structopt(default_value = "origin")]
+    remote: String,
+  },
+
+  /// Undo the last mutating command(s).
+  ///
+  /// Removing all tasks at once records one mutation per removed task.
+  Undo {
+    /// Number of mutations to undo.
+    #[structopt(default_value = "1")]
+    number: usize,
+  },
+
+  /// Add or remove dependencies on the task addressed by the top-level task UID.
+  Depend {
+    /// UIDs of the tasks to depend on.
+    on: Vec<UID>,
+
+    /// Remove the given dependencies instead of adding them.
+    #[structopt(long)]
+    unset: bool,
+  },
+
   /// List all the tasks.
   #[structopt(visible_aliases = &["l", "ls"])]
   List {
@@ -100,6 +130,22 @@ pub enum SubCommand {
     /// Show the content of each listed task, if any.
     #[structopt(long)]
     content: bool,
+
+    /// Only show tasks that have a due date.
+    #[structopt(long)]
+    due: bool,
+
+    /// Only show tasks that are overdue.
+    #[structopt(long)]
+    overdue: bool,
+
+    /// Only show tasks that are blocked by an unfinished dependency.
+    #[structopt(long)]
+    blocked: bool,
+
+    /// Only show tasks carrying all of the given tags. Can be repeated.
+    #[structopt(long = "tag")]
+    tags: Vec<String>,
   },
 }
 
@@ -110,6 +156,10 @@ pub fn list_tasks(
   start: bool,
   cancelled: bool,
   done: bool,
+  due: bool,
+  overdue: bool,
+  blocked: bool,
+  tags: Vec<String>,
 ) -> Result<(), Box<dyn Error>> {
   let task_mgr = TaskManager::new_from_config(config)?;
   let mut tasks: Vec<_> = task_mgr
@@ -123,18 +173,28 @@ pub fn list_tasks(
         Status::Cancelled => cancelled,
       }
     })
+    .filter(|(_, task)| !due || task.deadline().is_some())
+    .filter(|(_, task)| {
+      !overdue
+        || task
+          .deadline()
+          .is_some_and(|deadline| deadline.signed_duration_since(Utc::now()) < Duration::zero())
+    })
+    .filter(|(_, task)| !blocked || task_mgr.is_blocked(task))
+    .filter(|(_, task)| tags.iter().all(|tag| task.tags().contains(tag)))
     .collect();
   tasks.sort_by_key(|(_, task)| task.status());
 
-  // precompute a bunch of data for display widths / padding / etc.
-  let display_opts = DisplayOptions::new(config, tasks.iter().map(|&(uid, task)| (*uid, task)));
+  // build the table out of the rows that survived filtering, then render it
+  let rows: Vec<_> = tasks.iter().map(|&(&uid, task)| (uid, task)).collect();
+  let table = Table::build(config, &rows);
 
-  // actual display
-  display_task_header(config, &display_opts);
+  table.print_header();
 
   let mut parity = true;
   for (&uid, task) in tasks {
-    display_task_inline(config, uid, task, parity, &display_opts);
+    let blocked = task_mgr.is_blocked(task);
+    table.print_row(uid, task, parity, blocked);
 
     parity = !parity;
   }
@@ -167,13 +227,15 @@ pub fn add_task(
   }
 
   let uid = task_mgr.register_task(task.clone());
+  task_mgr.record_undo(vec![(uid, None)]);
   task_mgr.save(config)?;
 
-  // display options
-  let display_opts = DisplayOptions::new(config, once((uid, &task)));
+  // render the freshly added task on its own
+  let rows = [(uid, &task)];
+  let table = Table::build(config, &rows);
 
-  display_task_header(config, &display_opts);
-  display_task_inline(config, uid, &task, true, &display_opts);
+  table.print_header();
+  table.print_row(uid, &task, true, false);
 
   Ok(())
 }
@@ -195,240 +257,357 @@ pub fn edit_task(task: &mut Task, content: Vec<String>) -> Result<(), Box<dyn Er
   Ok(())
 }
 
-/// Display options to use when rendering in CLI.
-struct DisplayOptions {
-  /// Width of the task UID column.
-  task_uid_width: usize,
-  /// Width of the task status column.
-  status_width: usize,
-  /// Width of the task description column.
-  description_width: usize,
-  /// Width of the task project column.
-  project_width: usize,
-  /// Whether any task has spent time.
-  has_spent_time: bool,
-  /// Whether we have a priority in at least one task.
-  has_priorities: bool,
-  /// Whether we have a project in at least one task.
-  has_projects: bool,
-}
-
-impl DisplayOptions {
-  /// Create a new renderer for a set of tasks.
-  fn new<'a>(config: &Config, tasks: impl IntoIterator<Item = (UID, &'a Task)>) -> Self {
-    let (
-      task_uid_width,
-      status_width,
-      description_width,
-      project_width,
-      has_spent_time,
-      has_priorities,
-      has_projects,
-    ) = tasks.into_iter().fold(
-      (0, 0, 0, 0, false, false, false),
-      |(
-        task_uid_width,
-        status_width,
-        description_width,
-        project_width,
-        has_spent_time,
-        has_priorities,
-        has_projects,
-      ),
-       (uid, task)| {
-        let task_uid_width = task_uid_width.max(Self::guess_task_uid_width(uid));
-        let status_width = status_width.max(Self::guess_task_status_width(&config, task.status()));
-        let description_width = description_width.max(task.name().len());
-        let project_width = project_width.max(Self::guess_task_project_width(&task).unwrap_or(0));
-        let has_spent_tiem = has_spent_time || task.spent_time() != Duration::zero();
-        let has_priorities = has_priorities || task.priority().is_some();
-        let has_projects = has_projects || task.project().is_some();
-
-        (
-          task_uid_width,
-          status_width,
-          description_width,
-          project_width,
-          has_spent_time,
-          has_priorities,
-          has_projects,
-        )
-      },
-    );
+/// Ask the user to confirm an action by typing `y` or `yes`.
+pub fn confirm(prompt: &str) -> bool {
+  print!("{} [y/N] ", prompt);
+  io::stdout().flush().ok();
 
-    Self {
-      task_uid_width: task_uid_width.max(config.uid_col_name().len()),
-      status_width: status_width.max(config.status_col_name().len()),
-      description_width: description_width.max(config.description_col_name().len()),
-      project_width: project_width.max(config.project_col_name().len()),
-      has_spent_time,
-      has_priorities,
-      has_projects,
-    }
+  let mut answer = String::new();
+  if io::stdin().read_line(&mut answer).is_err() {
+    return false;
   }
 
-  /// Guess the width required to represent the task UID.
-  fn guess_task_uid_width(uid: UID) -> usize {
-    let val = uid.val();
-
-    if val < 10 {
-      1
-    } else if val < 100 {
-      2
-    } else if val < 1000 {
-      3
-    } else if val < 10000 {
-      4
-    } else if val < 100000 {
-      5
-    } else {
-      6
-    }
-  }
+  matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
 
-  /// Guess the width required to represent the task status.
-  fn guess_task_status_width(config: &Config, status: Status) -> usize {
-    let width = match status {
-      Status::Ongoing => config.wip_alias().len(),
-      Status::Todo => config.todo_alias().len(),
-      Status::Done => config.done_alias().len(),
-      Status::Cancelled => config.cancelled_alias().len(),
-    };
+/// Horizontal alignment of a column’s cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+  Left,
+}
+
+/// A column of the task table.
+///
+/// A column knows how to label itself, whether it should be shown at all for a given set of
+/// tasks, and how to render one of its cells. `cell` returns both a plain-text version (used to
+/// compute the column’s width) and the styled version actually printed.
+struct Column {
+  header: fn(&Config) -> String,
+  align: Alignment,
+  visible: fn(&Config, &[(UID, &Task)]) -> bool,
+  cell: fn(&Config, UID, &Task, bool, bool) -> (String, ColoredString),
+}
 
-    width.max("Status".len())
+/// Columns making up the task table, in display order.
+const COLUMNS: &[Column] = &[
+  Column {
+    header: uid_header,
+    align: Alignment::Left,
+    visible: always_visible,
+    cell: uid_cell,
+  },
+  Column {
+    header: age_header,
+    align: Alignment::Left,
+    visible: always_visible,
+    cell: age_cell,
+  },
+  Column {
+    header: spent_header,
+    align: Alignment::Left,
+    visible: has_spent_time,
+    cell: spent_cell,
+  },
+  Column {
+    header: prio_header,
+    align: Alignment::Left,
+    visible: has_priorities,
+    cell: priority_cell,
+  },
+  Column {
+    header: project_header,
+    align: Alignment::Left,
+    visible: has_projects,
+    cell: project_cell,
+  },
+  Column {
+    header: due_header,
+    align: Alignment::Left,
+    visible: has_due,
+    cell: due_cell,
+  },
+  Column {
+    header: tags_header,
+    align: Alignment::Left,
+    visible: has_tags,
+    cell: tags_cell,
+  },
+  Column {
+    header: status_header,
+    align: Alignment::Left,
+    visible: always_visible,
+    cell: status_cell,
+  },
+  Column {
+    header: description_header,
+    align: Alignment::Left,
+    visible: always_visible,
+    cell: description_cell,
+  },
+];
+
+fn always_visible(_: &Config, _: &[(UID, &Task)]) -> bool {
+  true
+}
+
+fn uid_header(config: &Config) -> String {
+  config.uid_col_name().to_owned()
+}
+
+fn uid_cell(_: &Config, uid: UID, _: &Task, _: bool, _: bool) -> (String, ColoredString) {
+  let text = uid.to_string();
+  (text.clone(), text.normal())
+}
+
+fn age_header(config: &Config) -> String {
+  config.age_col_name().to_owned()
+}
+
+fn age_cell(_: &Config, _: UID, task: &Task, _: bool, _: bool) -> (String, ColoredString) {
+  let text = friendly_task_age(task);
+  (text.clone(), text.normal())
+}
+
+fn spent_header(config: &Config) -> String {
+  config.spent_col_name().to_owned()
+}
+
+fn has_spent_time(_: &Config, tasks: &[(UID, &Task)]) -> bool {
+  tasks
+    .iter()
+    .any(|(_, task)| task.spent_time() != Duration::zero())
+}
+
+fn spent_cell(_: &Config, _: UID, task: &Task, _: bool, _: bool) -> (String, ColoredString) {
+  let dur = task.spent_time();
+
+  if dur == Duration::zero() {
+    return (String::new(), "".normal());
   }
 
-  fn guess_task_project_width(task: &Task) -> Option<usize> {
-    task.project().map(str::len)
+  let text = friendly_duration(dur);
+  let colored = match task.status() {
+    Status::Ongoing => text.clone().blue(),
+    _ => text.clone().bright_black().dimmed(),
+  };
+
+  (text, colored)
+}
+
+fn prio_header(config: &Config) -> String {
+  config.prio_col_name().to_owned()
+}
+
+fn has_priorities(_: &Config, tasks: &[(UID, &Task)]) -> bool {
+  tasks.iter().any(|(_, task)| task.priority().is_some())
+}
+
+fn priority_cell(_: &Config, _: UID, task: &Task, _: bool, _: bool) -> (String, ColoredString) {
+  match task.priority() {
+    Some(Priority::Low) => ("LOW".to_owned(), "LOW".bright_black().dimmed()),
+    Some(Priority::Medium) => ("MED".to_owned(), "MED".blue()),
+    Some(Priority::High) => ("HIGH".to_owned(), "HIGH".red()),
+    Some(Priority::Critical) => ("CRIT".to_owned(), "CRIT".black().on_bright_red()),
+    None => (String::new(), "".normal()),
   }
 }
 
-/// Display the header of tasks.
-fn display_task_header(config: &Config, opts: &DisplayOptions) {
-  print!(
-    " {uid:<uid_width$} {age:<age_width$}",
-    uid = config.uid_col_name().underline(),
-    uid_width = opts.task_uid_width,
-    age = config.age_col_name().underline(),
-    age_width = config.age_col_name().len(),
-  );
-
-  if opts.has_spent_time {
-    print!(
-      " {spent:<spent_width$}",
-      spent = config.spent_col_name().underline(),
-      spent_width = config.spent_col_name().len(),
-    );
+fn project_header(config: &Config) -> String {
+  config.project_col_name().to_owned()
+}
+
+fn has_projects(_: &Config, tasks: &[(UID, &Task)]) -> bool {
+  tasks.iter().any(|(_, task)| task.project().is_some())
+}
+
+fn project_cell(_: &Config, _: UID, task: &Task, _: bool, _: bool) -> (String, ColoredString) {
+  match task.project() {
+    Some(project) => (project.to_owned(), project.italic()),
+    None => (String::new(), "".normal()),
   }
+}
+
+fn due_header(config: &Config) -> String {
+  config.due_col_name().to_owned()
+}
 
-  if opts.has_priorities {
-    print!(
-      " {priority:<prio_width$}",
-      priority = config.prio_col_name().underline(),
-      prio_width = config.prio_col_name().len(),
-    );
+fn has_due(_: &Config, tasks: &[(UID, &Task)]) -> bool {
+  tasks.iter().any(|(_, task)| task.deadline().is_some())
+}
+
+/// Cell renderer for the due-date column, colored by urgency.
+///
+/// Overdue deadlines are red, deadlines within a day are bright red, deadlines within a few
+/// days are yellow, and anything further away is left uncolored.
+fn due_cell(_: &Config, _: UID, task: &Task, _: bool, _: bool) -> (String, ColoredString) {
+  match task.deadline() {
+    Some(deadline) => {
+      let text = deadline.format("%Y-%m-%d %H:%M").to_string();
+      let time_left = deadline.signed_duration_since(Utc::now());
+
+      let colored = if time_left < Duration::zero() {
+        text.clone().red()
+      } else if time_left < Duration::days(1) {
+        text.clone().bright_red()
+      } else if time_left < Duration::days(3) {
+        text.clone().yellow()
+      } else {
+        text.clone().normal()
+      };
+
+      (text, colored)
+    }
+    None => (String::new(), "".normal()),
   }
+}
 
-  if opts.has_projects {
-    print!(
-      " {project:<project_width$}",
-      project = config.project_col_name().underline(),
-      project_width = opts.project_width,
-    );
+fn tags_header(config: &Config) -> String {
+  config.tags_col_name().to_owned()
+}
+
+fn has_tags(_: &Config, tasks: &[(UID, &Task)]) -> bool {
+  tasks.iter().any(|(_, task)| !task.tags().is_empty())
+}
+
+fn tags_cell(_: &Config, _: UID, task: &Task, _: bool, _: bool) -> (String, ColoredString) {
+  if task.tags().is_empty() {
+    return (String::new(), "".normal());
   }
 
-  println!(
-    " {status:<status_width$} {description:<description_width$}",
-    status = config.status_col_name().underline(),
-    status_width = opts.status_width,
-    description = config.description_col_name().underline(),
-    description_width = opts.description_width,
-  );
+  let mut tags: Vec<_> = task.tags().iter().cloned().collect();
+  tags.sort();
+  let text = tags.join(",");
+
+  (text.clone(), text.cyan())
+}
+
+fn status_header(config: &Config) -> String {
+  config.status_col_name().to_owned()
 }
 
-/// Display a task to the user.
-fn display_task_inline(
+fn status_cell(
   config: &Config,
-  uid: UID,
+  _: UID,
+  task: &Task,
+  _: bool,
+  blocked: bool,
+) -> (String, ColoredString) {
+  let (text, colored) = match task.status() {
+    Status::Todo => (
+      config.todo_alias().clone(),
+      config.todo_alias().clone().bold().magenta(),
+    ),
+    Status::Ongoing => (
+      config.wip_alias().clone(),
+      config.wip_alias().clone().bold().green(),
+    ),
+    Status::Done => (
+      config.done_alias().clone(),
+      config.done_alias().clone().dimmed().bright_black(),
+    ),
+    Status::Cancelled => (
+      config.cancelled_alias().clone(),
+      config.cancelled_alias().clone().dimmed().bright_red(),
+    ),
+  };
+
+  (text, if blocked { colored.dimmed() } else { colored })
+}
+
+fn description_header(config: &Config) -> String {
+  config.description_col_name().to_owned()
+}
+
+fn description_cell(
+  _: &Config,
+  _: UID,
   task: &Task,
   parity: bool,
-  opts: &DisplayOptions,
-) {
-  let (name, status);
-  let task_status = task.status();
-
-  match task_status {
-    Status::Todo => {
-      if parity {
-        name = task.name().bright_white().on_black();
-      } else {
-        name = task.name().bright_white().on_bright_black();
-      }
-      status = config.todo_alias().clone().bold().magenta();
-    }
+  _: bool,
+) -> (String, ColoredString) {
+  let text = task.name().to_owned();
+
+  let colored = match task.status() {
+    Status::Todo if parity => text.clone().bright_white().on_black(),
+    Status::Todo => text.clone().bright_white().on_bright_black(),
+    Status::Ongoing => text.clone().black().on_bright_green(),
+    Status::Done => text.clone().bright_black().dimmed().on_black(),
+    Status::Cancelled => text
+      .clone()
+      .bright_black()
+      .dimmed()
+      .strikethrough()
+      .on_black(),
+  };
+
+  (text, colored)
+}
 
-    Status::Ongoing => {
-      name = task.name().black().on_bright_green();
-      status = config.wip_alias().clone().bold().green();
-    }
+/// A rendered table of tasks: the visible columns and their computed widths.
+struct Table<'c> {
+  config: &'c Config,
+  columns: Vec<&'static Column>,
+  widths: Vec<usize>,
+}
 
-    Status::Done => {
-      name = task.name().bright_black().dimmed().on_black();
-      status = config.done_alias().clone().dimmed().bright_black();
-    }
+impl<'c> Table<'c> {
+  /// Build a table for the given rows, deciding column visibility and widths from the data.
+  fn build(config: &'c Config, rows: &[(UID, &Task)]) -> Self {
+    let columns: Vec<_> = COLUMNS
+      .iter()
+      .filter(|column| (column.visible)(config, rows))
+      .collect();
+
+    let widths = columns
+      .iter()
+      .map(|column| {
+        let header_width = (column.header)(config).len();
+        let content_width = rows
+          .iter()
+          .map(|&(uid, task)| (column.cell)(config, uid, task, true, false).0.len())
+          .max()
+          .unwrap_or(0);
+
+        header_width.max(content_width)
+      })
+      .collect();
 
-    Status::Cancelled => {
-      name = task
-        .name()
-        .bright_black()
-        .dimmed()
-        .strikethrough()
-        .on_black();
-      status = config.cancelled_alias().clone().dimmed().bright_red();
+    Self {
+      config,
+      columns,
+      widths,
     }
   }
 
-  let spent_time = friendly_spent_time(task.spent_time(), task_status);
-
-  print!(
-    " {uid:<uid_width$} {age:<age_width$}",
-    uid = uid,
-    uid_width = opts.task_uid_width,
-    age = friendly_task_age(task),
-    age_width = config.age_col_name().len(),
-  );
-
-  if opts.has_spent_time {
-    print!(
-      " {spent:<spent_width$}",
-      spent = spent_time,
-      spent_width = config.spent_col_name().len(),
-    );
-  }
+  /// Print the header row.
+  fn print_header(&self) {
+    let mut line = String::new();
 
-  if opts.has_priorities {
-    print!(
-      " {priority:<prio_width$}",
-      priority = friendly_priority(task),
-      prio_width = config.prio_col_name().len(),
-    );
-  }
+    for (column, &width) in self.columns.iter().zip(&self.widths) {
+      let header = (column.header)(self.config).underline();
+      line.push(' ');
+      line.push_str(&match column.align {
+        Alignment::Left => format!("{:<width$}", header, width = width),
+      });
+    }
 
-  if opts.has_projects {
-    print!(
-      " {project:<project_width$}",
-      project = friendly_project(task),
-      project_width = opts.project_width,
-    );
+    println!("{}", line);
   }
 
-  println!(
-    " {status:<status_width$} {name:<name_width$}",
-    status = status,
-    status_width = opts.status_width,
-    name = name,
-    name_width = opts.description_width,
-  );
+  /// Print a single task row.
+  fn print_row(&self, uid: UID, task: &Task, parity: bool, blocked: bool) {
+    let mut line = String::new();
+
+    for (column, &width) in self.columns.iter().zip(&self.widths) {
+      let (_, colored) = (column.cell)(self.config, uid, task, parity, blocked);
+      line.push(' ');
+      line.push_str(&match column.align {
+        Alignment::Left => format!("{:<width$}", colored, width = width),
+      });
+    }
+
+    println!("{}", line);
+  }
 }
 
 /// Find out the age of a task and get a friendly representation.
@@ -455,39 +634,3 @@ pub fn friendly_duration(dur: Duration) -> String {
   }
 }
 
-fn friendly_priority(task: &Task) -> impl Display {
-  if let Some(prio) = task.priority() {
-    match prio {
-      Priority::Low => "LOW".bright_black().dimmed(),
-      Priority::Medium => "MED".blue(),
-      Priority::High => "HIGH".red(),
-      Priority::Critical => "CRIT".black().on_bright_red(),
-    }
-  } else {
-    "".normal()
-  }
-}
-
-fn friendly_project(task: &Task) -> impl Display {
-  if let Some(project) = task.project() {
-    project.italic()
-  } else {
-    "".normal()
-  }
-}
-
-/// String representation of a spent-time.
-///
-/// If no time has been spent on this task, an empty string is returned.
-fn friendly_spent_time(dur: Duration, status: Status) -> impl Display {
-  if dur == Duration::zero() {
-    return String::new().normal();
-  }
-
-  let output = friendly_duration(dur);
-
-  match status {
-    Status::Ongoing => output.blue(),
-    _ => output.bright_black().dimmed(),
-  }
-}
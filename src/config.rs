@@ -0,0 +1,154 @@
+//! User configuration.
+
+use serde::{Deserialize, Serialize};
+use std::{
+  error::Error,
+  fs,
+  path::{Path, PathBuf},
+};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+const DEFAULT_TASKS_FILE_NAME: &str = "tasks.json";
+const DEFAULT_HISTORY_FILE_NAME: &str = "history.json";
+
+/// User configuration, holding the data directory along with display preferences.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+  #[serde(skip)]
+  root_dir: PathBuf,
+
+  tasks_file_name: String,
+  history_file_name: String,
+
+  uid_col_name: String,
+  age_col_name: String,
+  spent_col_name: String,
+  prio_col_name: String,
+  project_col_name: String,
+  due_col_name: String,
+  tags_col_name: String,
+  status_col_name: String,
+  description_col_name: String,
+
+  todo_alias: String,
+  wip_alias: String,
+  done_alias: String,
+  cancelled_alias: String,
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Config {
+      root_dir: PathBuf::new(),
+      tasks_file_name: DEFAULT_TASKS_FILE_NAME.to_owned(),
+      history_file_name: DEFAULT_HISTORY_FILE_NAME.to_owned(),
+      uid_col_name: "UID".to_owned(),
+      age_col_name: "Age".to_owned(),
+      spent_col_name: "Spent".to_owned(),
+      prio_col_name: "Priority".to_owned(),
+      project_col_name: "Project".to_owned(),
+      due_col_name: "Due".to_owned(),
+      tags_col_name: "Tags".to_owned(),
+      status_col_name: "Status".to_owned(),
+      description_col_name: "Description".to_owned(),
+      todo_alias: "TODO".to_owned(),
+      wip_alias: "WIP".to_owned(),
+      done_alias: "DONE".to_owned(),
+      cancelled_alias: "CANC".to_owned(),
+    }
+  }
+}
+
+impl Config {
+  /// Get the configuration, reading it from `config_root` if provided, falling back to the
+  /// platform-specific default location otherwise.
+  pub fn get(config_root: Option<PathBuf>) -> Result<Self, Box<dyn Error>> {
+    let root_dir = match config_root {
+      Some(root) => root,
+      None => directories::ProjectDirs::from("org", "toodoux", "toodoux")
+        .ok_or("cannot determine the default configuration directory")?
+        .config_dir()
+        .to_owned(),
+    };
+
+    fs::create_dir_all(&root_dir)?;
+
+    let config_path = root_dir.join(CONFIG_FILE_NAME);
+    let mut config: Config = if config_path.exists() {
+      toml::from_str(&fs::read_to_string(&config_path)?)?
+    } else {
+      Config::default()
+    };
+
+    config.root_dir = root_dir;
+    Ok(config)
+  }
+
+  /// Root directory holding the configuration and the task store.
+  pub fn root_dir(&self) -> &Path {
+    &self.root_dir
+  }
+
+  /// Path of the file the tasks are persisted to.
+  pub fn tasks_path(&self) -> PathBuf {
+    self.root_dir.join(&self.tasks_file_name)
+  }
+
+  /// Path of the file the undo journal is persisted to.
+  pub fn history_path(&self) -> PathBuf {
+    self.root_dir.join(&self.history_file_name)
+  }
+
+  pub fn uid_col_name(&self) -> &str {
+    &self.uid_col_name
+  }
+
+  pub fn age_col_name(&self) -> &str {
+    &self.age_col_name
+  }
+
+  pub fn spent_col_name(&self) -> &str {
+    &self.spent_col_name
+  }
+
+  pub fn prio_col_name(&self) -> &str {
+    &self.prio_col_name
+  }
+
+  pub fn project_col_name(&self) -> &str {
+    &self.project_col_name
+  }
+
+  pub fn due_col_name(&self) -> &str {
+    &self.due_col_name
+  }
+
+  pub fn tags_col_name(&self) -> &str {
+    &self.tags_col_name
+  }
+
+  pub fn status_col_name(&self) -> &str {
+    &self.status_col_name
+  }
+
+  pub fn description_col_name(&self) -> &str {
+    &self.description_col_name
+  }
+
+  pub fn todo_alias(&self) -> &String {
+    &self.todo_alias
+  }
+
+  pub fn wip_alias(&self) -> &String {
+    &self.wip_alias
+  }
+
+  pub fn done_alias(&self) -> &String {
+    &self.done_alias
+  }
+
+  pub fn cancelled_alias(&self) -> &String {
+    &self.cancelled_alias
+  }
+}
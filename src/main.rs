@@ -0,0 +1,24 @@
+mod cli;
+mod config;
+mod metadata;
+mod subcmd;
+mod sync;
+mod task;
+
+use std::error::Error;
+use structopt::StructOpt;
+
+use cli::Command;
+use config::Config;
+use subcmd::run_subcmd;
+
+fn main() -> Result<(), Box<dyn Error>> {
+  let Command {
+    task_uid,
+    subcmd,
+    config,
+  } = Command::from_args();
+  let config = Config::get(config)?;
+
+  run_subcmd(config, subcmd, task_uid)
+}
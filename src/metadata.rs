@@ -0,0 +1,181 @@
+//! Task metadata, parsed out of free-form content words.
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt};
+
+/// Priority of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Priority {
+  Low,
+  Medium,
+  High,
+  Critical,
+}
+
+/// A single piece of metadata extracted from a task’s content.
+#[derive(Debug, Clone)]
+pub enum Metadata {
+  /// `@project`
+  Project(String),
+  /// `!priority`
+  Priority(Priority),
+  /// `due:<natural language date>`
+  Due(DateTime<Utc>),
+  /// `+tag`
+  Tag(String),
+}
+
+/// Error that can occur while parsing or validating metadata.
+#[derive(Debug)]
+pub enum MetadataError {
+  UnknownPriority(String),
+  InvalidDue(String),
+  MultipleProjects,
+  MultiplePriorities,
+  MultipleDues,
+}
+
+impl fmt::Display for MetadataError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      MetadataError::UnknownPriority(p) => write!(f, "unknown priority: {}", p),
+      MetadataError::InvalidDue(d) => write!(f, "cannot parse due date: {}", d),
+      MetadataError::MultipleProjects => write!(f, "a task cannot belong to several projects"),
+      MetadataError::MultiplePriorities => write!(f, "a task cannot have several priorities"),
+      MetadataError::MultipleDues => write!(f, "a task cannot have several due dates"),
+    }
+  }
+}
+
+impl Error for MetadataError {}
+
+impl Metadata {
+  /// Extract metadata out of a sequence of content words, returning the leftover words joined
+  /// back as the task’s name.
+  pub fn from_words<'a>(words: impl Iterator<Item = &'a str>) -> (Vec<Metadata>, String) {
+    let mut metadata = Vec::new();
+    let mut name_words = Vec::new();
+
+    for word in words {
+      if let Some(project) = word.strip_prefix('@') {
+        metadata.push(Metadata::Project(project.to_owned()));
+      } else if let Some(prio) = word.strip_prefix('!') {
+        if let Some(priority) = parse_priority(prio) {
+          metadata.push(Metadata::Priority(priority));
+        } else {
+          name_words.push(word);
+        }
+      } else if let Some(due) = word.strip_prefix("due:") {
+        if let Ok(deadline) = parse_natural_date(due) {
+          metadata.push(Metadata::Due(deadline));
+        } else {
+          name_words.push(word);
+        }
+      } else if let Some(tag) = word.strip_prefix('+') {
+        metadata.push(Metadata::Tag(tag.to_owned()));
+      } else {
+        name_words.push(word);
+      }
+    }
+
+    (metadata, name_words.join(" "))
+  }
+
+  /// Ensure a batch of parsed metadata is internally consistent (e.g. a single project, a
+  /// single priority, a single due date).
+  pub fn validate(metadata: &[Metadata]) -> Result<(), MetadataError> {
+    let mut projects = 0;
+    let mut priorities = 0;
+    let mut dues = 0;
+
+    for meta in metadata {
+      match meta {
+        Metadata::Project(_) => projects += 1,
+        Metadata::Priority(_) => priorities += 1,
+        Metadata::Due(_) => dues += 1,
+        // tags are multi-valued, so any number of them is valid
+        Metadata::Tag(_) => {}
+      }
+    }
+
+    if projects > 1 {
+      Err(MetadataError::MultipleProjects)
+    } else if priorities > 1 {
+      Err(MetadataError::MultiplePriorities)
+    } else if dues > 1 {
+      Err(MetadataError::MultipleDues)
+    } else {
+      Ok(())
+    }
+  }
+}
+
+fn parse_priority(s: &str) -> Option<Priority> {
+  match s.to_lowercase().as_str() {
+    "low" => Some(Priority::Low),
+    "medium" | "med" => Some(Priority::Medium),
+    "high" => Some(Priority::High),
+    "critical" | "crit" => Some(Priority::Critical),
+    _ => None,
+  }
+}
+
+/// Parse a natural-language deadline expression, resolved against [`Utc::now()`].
+///
+/// Accepts relative expressions (`today`, `tomorrow`, `in 3 days`), weekday names (`friday`),
+/// and ISO dates (`2021-09-03`).
+pub fn parse_natural_date(s: &str) -> Result<DateTime<Utc>, MetadataError> {
+  let trimmed = s.trim().trim_matches('"').to_lowercase();
+
+  if trimmed.is_empty() {
+    return Err(MetadataError::InvalidDue(s.to_owned()));
+  }
+
+  let now = Utc::now();
+
+  match trimmed.as_str() {
+    "today" => return Ok(now),
+    "tomorrow" => return Ok(now + Duration::days(1)),
+    _ => {}
+  }
+
+  if let Some(rest) = trimmed
+    .strip_prefix("in ")
+    .and_then(|rest| rest.strip_suffix(" days").or_else(|| rest.strip_suffix(" day")))
+  {
+    if let Ok(n) = rest.trim().parse::<i64>() {
+      return Ok(now + Duration::days(n));
+    }
+  }
+
+  let weekday = trimmed.strip_prefix("next ").unwrap_or(&trimmed);
+  if let Some(target) = parse_weekday(weekday) {
+    let today = target_weekday_offset(now.weekday(), target);
+    let offset = if today == 0 { 7 } else { today };
+    return Ok(now + Duration::days(offset as i64));
+  }
+
+  if let Ok(date) = chrono::NaiveDate::parse_from_str(&trimmed, "%Y-%m-%d") {
+    return Ok(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap()));
+  }
+
+  Err(MetadataError::InvalidDue(s.to_owned()))
+}
+
+fn parse_weekday(s: &str) -> Option<Weekday> {
+  match s {
+    "monday" => Some(Weekday::Mon),
+    "tuesday" => Some(Weekday::Tue),
+    "wednesday" => Some(Weekday::Wed),
+    "thursday" => Some(Weekday::Thu),
+    "friday" => Some(Weekday::Fri),
+    "saturday" => Some(Weekday::Sat),
+    "sunday" => Some(Weekday::Sun),
+    _ => None,
+  }
+}
+
+fn target_weekday_offset(from: Weekday, to: Weekday) -> u32 {
+  (to.num_days_from_monday() + 7 - from.num_days_from_monday()) % 7
+}
@@ -3,8 +3,9 @@ use std::error::Error;
 use colored::Colorize;
 
 use crate::{
-  cli::{add_task, edit_task, list_tasks, SubCommand},
+  cli::{add_task, confirm, edit_task, list_tasks, SubCommand},
   config::Config,
+  sync::sync,
   task::{Status, TaskManager, UID},
 };
 
@@ -16,7 +17,18 @@ pub fn run_subcmd(
   match subcmd {
     // default subcommand
     None => {
-      default_list(&config, true, true, false, false, false)?;
+      default_list(
+        &config,
+        true,
+        true,
+        false,
+        false,
+        false,
+        false,
+        false,
+        false,
+        Vec::new(),
+      )?;
     }
 
     Some(subcmd) => {
@@ -42,7 +54,9 @@ pub fn run_subcmd(
 
         SubCommand::Edit { content } => {
           if let Some(task) = task {
+            let previous = task.clone();
             edit_task(task, content)?;
+            task_mgr.record_undo(vec![(task_uid.unwrap(), Some(previous))]);
             task_mgr.save(&config)?;
           } else {
             println!("{}", "missing or unknown task to edit".red());
@@ -51,7 +65,9 @@ pub fn run_subcmd(
 
         SubCommand::Todo => {
           if let Some(task) = task {
+            let previous = task.clone();
             task.change_status(Status::Todo);
+            task_mgr.record_undo(vec![(task_uid.unwrap(), Some(previous))]);
             task_mgr.save(&config)?;
           } else {
             println!("{}", "missing or unknown task".red());
@@ -60,7 +76,9 @@ pub fn run_subcmd(
 
         SubCommand::Start => {
           if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
+            let previous = task.clone();
             task.change_status(Status::Ongoing);
+            task_mgr.record_undo(vec![(task_uid.unwrap(), Some(previous))]);
             task_mgr.save(&config)?;
           } else {
             println!("{}", "missing or unknown task to start".red());
@@ -69,7 +87,9 @@ pub fn run_subcmd(
 
         SubCommand::Done => {
           if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
+            let previous = task.clone();
             task.change_status(Status::Done);
+            task_mgr.record_undo(vec![(task_uid.unwrap(), Some(previous))]);
             task_mgr.save(&config)?;
           } else {
             println!("{}", "missing or unknown task to finish".red());
@@ -78,14 +98,66 @@ pub fn run_subcmd(
 
         SubCommand::Cancel => {
           if let Some(task) = task_uid.and_then(|uid| task_mgr.get_mut(uid)) {
+            let previous = task.clone();
             task.change_status(Status::Cancelled);
+            task_mgr.record_undo(vec![(task_uid.unwrap(), Some(previous))]);
             task_mgr.save(&config)?;
           } else {
             println!("{}", "missing or unknown task to cancel".red());
           }
         }
 
-        SubCommand::Remove { .. } => {}
+        SubCommand::Sync { remote } => {
+          sync(&config, &remote)?;
+        }
+
+        SubCommand::Undo { number } => {
+          let undone = task_mgr.undo(number);
+          task_mgr.save(&config)?;
+          println!("undid {} mutation(s)", undone);
+        }
+
+        SubCommand::Remove { all } => {
+          if all {
+            if confirm("remove all tasks?") {
+              let uids: Vec<_> = task_mgr.tasks().map(|(&uid, _)| uid).collect();
+
+              for uid in uids {
+                task_mgr.remove(uid);
+              }
+
+              task_mgr.save(&config)?;
+            }
+          } else if let Some(uid) = task_uid {
+            match task_mgr.remove(uid) {
+              Some(_) => {
+                task_mgr.save(&config)?;
+              }
+              None => println!("{}", "missing or unknown task to remove".red()),
+            }
+          } else {
+            println!("{}", "missing or unknown task to remove".red());
+          }
+        }
+
+        SubCommand::Depend { on, unset } => {
+          if let Some(task_uid) = task_uid {
+            let previous = task_mgr.get_mut(task_uid).map(|task| task.clone());
+            for dep in on {
+              if unset {
+                task_mgr.remove_dependency(task_uid, dep)?;
+              } else {
+                task_mgr.add_dependency(task_uid, dep)?;
+              }
+            }
+            if let Some(previous) = previous {
+              task_mgr.record_undo(vec![(task_uid, Some(previous))]);
+            }
+            task_mgr.save(&config)?;
+          } else {
+            println!("{}", "missing or unknown task to set dependencies on".red());
+          }
+        }
 
         SubCommand::List {
           todo,
@@ -93,9 +165,15 @@ pub fn run_subcmd(
           done,
           cancelled,
           all,
+          due,
+          overdue,
+          blocked,
+          tags,
           ..
         } => {
-          default_list(&config, todo, start, cancelled, done, all)?;
+          default_list(
+            &config, todo, start, cancelled, done, all, due, overdue, blocked, tags,
+          )?;
         }
       }
     }
@@ -111,6 +189,10 @@ fn default_list(
   mut cancelled: bool,
   mut done: bool,
   all: bool,
+  due: bool,
+  overdue: bool,
+  blocked: bool,
+  tags: Vec<String>,
 ) -> Result<(), Box<dyn Error>> {
   // handle filtering logic
   if all {
@@ -124,5 +206,5 @@ fn default_list(
     start = true;
   }
 
-  list_tasks(config, todo, start, cancelled, done)
+  list_tasks(config, todo, start, cancelled, done, due, overdue, blocked, tags)
 }
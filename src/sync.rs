@@ -0,0 +1,95 @@
+//! Git-backed synchronization of the task store.
+
+use chrono::Utc;
+use std::{
+  error::Error,
+  fmt,
+  path::Path,
+  process::{Command, Output},
+};
+
+use crate::config::Config;
+
+/// Error that can occur while synchronizing the task store with a git remote.
+#[derive(Debug)]
+pub enum SyncError {
+  /// A merge conflict occurred while pulling from the remote.
+  MergeConflict(String),
+  /// A git command failed for another reason.
+  GitFailed(String),
+}
+
+impl fmt::Display for SyncError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SyncError::MergeConflict(details) => {
+        write!(f, "sync failed because of a merge conflict:\n{}", details)
+      }
+      SyncError::GitFailed(details) => write!(f, "git command failed:\n{}", details),
+    }
+  }
+}
+
+impl Error for SyncError {}
+
+/// Version-control the config root and synchronize it with `remote`.
+///
+/// This initializes a git repository in the config root if one doesn’t already exist, commits
+/// the current state of the task store, then pulls and pushes against `remote`.
+pub fn sync(config: &Config, remote: &str) -> Result<(), Box<dyn Error>> {
+  let root = config.root_dir();
+
+  if !root.join(".git").exists() {
+    run_git(root, &["init"])?;
+  }
+
+  run_git(root, &["add", "-A"])?;
+
+  let message = format!("toodoux sync {}", Utc::now().to_rfc3339());
+  let commit = git(root, &["commit", "-m", &message])?;
+  if !commit.status.success()
+    && !stdout_of(&commit).contains("nothing to commit")
+    && !stderr_of(&commit).contains("nothing to commit")
+  {
+    return Err(Box::new(SyncError::GitFailed(stderr_of(&commit))));
+  }
+
+  let pull = git(root, &["pull", remote])?;
+  if !pull.status.success() {
+    // git reports merge conflicts on stdout and other failures on stderr
+    let details = format!("{}{}", stdout_of(&pull), stderr_of(&pull));
+    return if details.contains("CONFLICT") || details.contains("conflict") {
+      Err(Box::new(SyncError::MergeConflict(details)))
+    } else {
+      Err(Box::new(SyncError::GitFailed(details)))
+    };
+  }
+
+  run_git(root, &["push", remote])?;
+
+  Ok(())
+}
+
+/// Run a git command in `root`, returning an error if it couldn’t even be spawned.
+fn git(root: &Path, args: &[&str]) -> Result<Output, Box<dyn Error>> {
+  Ok(Command::new("git").current_dir(root).args(args).output()?)
+}
+
+/// Run a git command in `root`, turning a non-zero exit status into a [`SyncError::GitFailed`].
+fn run_git(root: &Path, args: &[&str]) -> Result<Output, Box<dyn Error>> {
+  let output = git(root, args)?;
+
+  if !output.status.success() {
+    return Err(Box::new(SyncError::GitFailed(stderr_of(&output))));
+  }
+
+  Ok(output)
+}
+
+fn stdout_of(output: &Output) -> String {
+  String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr_of(output: &Output) -> String {
+  String::from_utf8_lossy(&output.stderr).into_owned()
+}
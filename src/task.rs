@@ -0,0 +1,421 @@
+//! Task model and storage.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{HashMap, HashSet},
+  error::Error,
+  fmt::{self, Display},
+  fs,
+  str::FromStr,
+};
+
+use crate::{config::Config, metadata::Metadata, metadata::Priority};
+
+/// Unique identifier of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UID(usize);
+
+impl UID {
+  /// Get the raw value of the UID.
+  pub fn val(&self) -> usize {
+    self.0
+  }
+}
+
+impl Display for UID {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.0)
+  }
+}
+
+impl FromStr for UID {
+  type Err = std::num::ParseIntError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    s.parse().map(UID)
+  }
+}
+
+/// Status of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Status {
+  Todo,
+  Ongoing,
+  Done,
+  Cancelled,
+}
+
+/// A single entry in a task’s status history.
+///
+/// Used to compute how much time was spent on a task while it was [`Status::Ongoing`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Event {
+  date: DateTime<Utc>,
+  status: Status,
+}
+
+/// A task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+  name: String,
+  status: Status,
+  history: Vec<Event>,
+  project: Option<String>,
+  priority: Option<Priority>,
+  /// Deadline the task should be completed by, if any.
+  deadline: Option<DateTime<Utc>>,
+  /// Other tasks this task depends on, which must be completed first.
+  depends_on: Vec<UID>,
+  /// Free-form tags attached to the task.
+  tags: HashSet<String>,
+}
+
+impl Task {
+  /// Create a new task with an optional seed history.
+  ///
+  /// The history is usually empty for a fresh task; a [`Status::Todo`] creation event is
+  /// automatically prepended to it.
+  pub fn new(name: impl Into<String>, mut history: Vec<Event>) -> Self {
+    history.insert(
+      0,
+      Event {
+        date: Utc::now(),
+        status: Status::Todo,
+      },
+    );
+
+    Task {
+      name: name.into(),
+      status: Status::Todo,
+      history,
+      project: None,
+      priority: None,
+      deadline: None,
+      depends_on: Vec::new(),
+      tags: HashSet::new(),
+    }
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn change_name(&mut self, name: impl Into<String>) {
+    self.name = name.into();
+  }
+
+  pub fn status(&self) -> Status {
+    self.status
+  }
+
+  pub fn change_status(&mut self, status: Status) {
+    self.status = status;
+    self.history.push(Event {
+      date: Utc::now(),
+      status,
+    });
+  }
+
+  pub fn project(&self) -> Option<&str> {
+    self.project.as_deref()
+  }
+
+  pub fn priority(&self) -> Option<Priority> {
+    self.priority
+  }
+
+  /// Deadline the task should be completed by, if any.
+  pub fn deadline(&self) -> Option<&DateTime<Utc>> {
+    self.deadline.as_ref()
+  }
+
+  pub fn creation_date(&self) -> Option<&DateTime<Utc>> {
+    self.history.first().map(|event| &event.date)
+  }
+
+  /// Other tasks this task depends on.
+  pub fn depends_on(&self) -> &[UID] {
+    &self.depends_on
+  }
+
+  /// Free-form tags attached to the task.
+  pub fn tags(&self) -> &HashSet<String> {
+    &self.tags
+  }
+
+  /// Total time spent while the task was [`Status::Ongoing`].
+  pub fn spent_time(&self) -> Duration {
+    let mut spent = Duration::zero();
+    let mut ongoing_since = None;
+
+    for event in &self.history {
+      match event.status {
+        Status::Ongoing => ongoing_since = Some(event.date),
+        _ => {
+          if let Some(since) = ongoing_since.take() {
+            spent = spent + event.date.signed_duration_since(since);
+          }
+        }
+      }
+    }
+
+    if let Some(since) = ongoing_since {
+      spent = spent + Utc::now().signed_duration_since(since);
+    }
+
+    spent
+  }
+
+  /// Apply a batch of parsed [`Metadata`] to this task.
+  pub fn apply_metadata(&mut self, metadata: Vec<Metadata>) {
+    for meta in metadata {
+      match meta {
+        Metadata::Project(project) => self.project = Some(project),
+        Metadata::Priority(priority) => self.priority = Some(priority),
+        Metadata::Due(deadline) => self.deadline = Some(deadline),
+        Metadata::Tag(tag) => {
+          self.tags.insert(tag);
+        }
+      }
+    }
+  }
+}
+
+/// Error that can occur while mutating the task graph.
+#[derive(Debug)]
+pub enum TaskError {
+  /// The referenced task doesn’t exist.
+  UnknownTask(UID),
+  /// Adding the dependency would create a cycle.
+  CyclicDependency { task: UID, on: UID },
+}
+
+impl fmt::Display for TaskError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TaskError::UnknownTask(uid) => write!(f, "unknown task {}", uid),
+      TaskError::CyclicDependency { task, on } => write!(
+        f,
+        "task {} cannot depend on task {} as it would create a circular dependency",
+        task, on
+      ),
+    }
+  }
+}
+
+impl Error for TaskError {}
+
+/// Maximum number of entries kept in the undo journal.
+const MAX_JOURNAL_LEN: usize = 100;
+
+/// A single undoable mutation, recording the prior state of every task it affected.
+///
+/// A `previous` of `None` means the task didn’t exist before the mutation (i.e. it was an
+/// addition), acting as a tombstone: undoing it removes the task again. Most mutations only
+/// affect a single task, but some (e.g. removing a task that others depend on) ripple into
+/// several, and must be undone together as one unit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+  changes: Vec<(UID, Option<Task>)>,
+}
+
+/// Manager of the tasks, responsible for loading, persisting and indexing them.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TaskManager {
+  tasks: HashMap<UID, Task>,
+  #[serde(default)]
+  next_uid: usize,
+  #[serde(skip)]
+  journal: Vec<JournalEntry>,
+}
+
+impl TaskManager {
+  /// Load the task manager from the store pointed at by `config`, creating an empty one if it
+  /// doesn’t exist yet.
+  pub fn new_from_config(config: &Config) -> Result<Self, Box<dyn Error>> {
+    let path = config.tasks_path();
+
+    let mut task_mgr: TaskManager = if path.exists() {
+      let content = fs::read_to_string(path)?;
+      serde_json::from_str(&content)?
+    } else {
+      TaskManager::default()
+    };
+
+    let history_path = config.history_path();
+    if history_path.exists() {
+      task_mgr.journal = serde_json::from_str(&fs::read_to_string(history_path)?)?;
+    }
+
+    Ok(task_mgr)
+  }
+
+  /// Persist the tasks and the undo journal to the store pointed at by `config`.
+  pub fn save(&self, config: &Config) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(config.root_dir())?;
+    fs::write(config.tasks_path(), serde_json::to_string_pretty(self)?)?;
+    fs::write(
+      config.history_path(),
+      serde_json::to_string_pretty(&self.journal)?,
+    )?;
+    Ok(())
+  }
+
+  /// Record the prior state of every task affected by a mutation, so it can later be undone as a
+  /// single unit.
+  ///
+  /// Pass `None` for a task’s previous state when the mutation is an addition (the task didn’t
+  /// exist before). Does nothing if `changes` is empty.
+  pub fn record_undo(&mut self, changes: Vec<(UID, Option<Task>)>) {
+    if changes.is_empty() {
+      return;
+    }
+
+    self.journal.push(JournalEntry { changes });
+
+    if self.journal.len() > MAX_JOURNAL_LEN {
+      self.journal.remove(0);
+    }
+  }
+
+  /// Undo the last `number` recorded mutations, restoring every task each one affected to its
+  /// prior state.
+  ///
+  /// Returns the number of mutations actually undone, which may be less than `number` if the
+  /// journal doesn’t hold that many entries.
+  pub fn undo(&mut self, number: usize) -> usize {
+    let mut undone = 0;
+
+    for _ in 0..number {
+      let entry = match self.journal.pop() {
+        Some(entry) => entry,
+        None => break,
+      };
+
+      for (uid, previous) in entry.changes {
+        match previous {
+          Some(task) => {
+            self.tasks.insert(uid, task);
+          }
+          None => {
+            self.tasks.remove(&uid);
+          }
+        }
+      }
+
+      undone += 1;
+    }
+
+    undone
+  }
+
+  /// Register a new task, assigning it a fresh [`UID`].
+  pub fn register_task(&mut self, task: Task) -> UID {
+    let uid = UID(self.next_uid);
+    self.next_uid += 1;
+    self.tasks.insert(uid, task);
+
+    uid
+  }
+
+  pub fn get_mut(&mut self, uid: UID) -> Option<&mut Task> {
+    self.tasks.get_mut(&uid)
+  }
+
+  pub fn tasks(&self) -> impl Iterator<Item = (&UID, &Task)> {
+    self.tasks.iter()
+  }
+
+  /// Make `task_uid` depend on `on`, rejecting the edge if it would introduce a cycle.
+  pub fn add_dependency(&mut self, task_uid: UID, on: UID) -> Result<(), TaskError> {
+    if !self.tasks.contains_key(&task_uid) {
+      return Err(TaskError::UnknownTask(task_uid));
+    }
+
+    if !self.tasks.contains_key(&on) {
+      return Err(TaskError::UnknownTask(on));
+    }
+
+    // `on` would become a dependency of `task_uid`; reject the edge if `on` can already
+    // (transitively) reach `task_uid`, as that would close a cycle.
+    if task_uid == on || self.is_reachable(on, task_uid) {
+      return Err(TaskError::CyclicDependency { task: task_uid, on });
+    }
+
+    let task = self.tasks.get_mut(&task_uid).unwrap();
+    if !task.depends_on.contains(&on) {
+      task.depends_on.push(on);
+    }
+
+    Ok(())
+  }
+
+  /// Remove a dependency edge, if present.
+  pub fn remove_dependency(&mut self, task_uid: UID, on: UID) -> Result<(), TaskError> {
+    let task = self
+      .tasks
+      .get_mut(&task_uid)
+      .ok_or(TaskError::UnknownTask(task_uid))?;
+    task.depends_on.retain(|&dep| dep != on);
+
+    Ok(())
+  }
+
+  /// Whether `target` is reachable from `start` by following `depends_on` edges.
+  fn is_reachable(&self, start: UID, target: UID) -> bool {
+    let mut visited = HashSet::new();
+    let mut stack = vec![start];
+
+    while let Some(uid) = stack.pop() {
+      if uid == target {
+        return true;
+      }
+
+      if !visited.insert(uid) {
+        continue;
+      }
+
+      if let Some(task) = self.tasks.get(&uid) {
+        stack.extend(task.depends_on.iter().copied());
+      }
+    }
+
+    false
+  }
+
+  /// Whether a task is blocked, i.e. at least one of its dependencies isn’t [`Status::Done`] or
+  /// [`Status::Cancelled`] yet.
+  pub fn is_blocked(&self, task: &Task) -> bool {
+    task.depends_on.iter().any(|dep| {
+      !matches!(
+        self.tasks.get(dep).map(Task::status),
+        Some(Status::Done) | Some(Status::Cancelled)
+      )
+    })
+  }
+
+  /// Remove a task, detaching it from any dependency edges that reference it.
+  ///
+  /// The removal and every dependent edge it touched are journaled together as a single undoable
+  /// mutation, so undoing it restores both the task and the edges that pointed at it.
+  pub fn remove(&mut self, uid: UID) -> Option<Task> {
+    let mut changes = Vec::new();
+
+    for (&dependent, task) in self.tasks.iter_mut() {
+      if task.depends_on.contains(&uid) {
+        changes.push((dependent, Some(task.clone())));
+        task.depends_on.retain(|&dep| dep != uid);
+      }
+    }
+
+    let removed = self.tasks.remove(&uid);
+    if let Some(removed) = &removed {
+      changes.push((uid, Some(removed.clone())));
+    }
+
+    self.record_undo(changes);
+
+    removed
+  }
+}